@@ -0,0 +1,271 @@
+//! Asynchronous mirror of [`StagedFile`](crate::StagedFile) for use inside
+//! async executors.
+//!
+//! [`AsyncStagedFile`] has the same staging semantics as [`StagedFile`](crate::StagedFile),
+//! but the blocking syscalls (creating the temporary directory and file, the
+//! final `sync_all`, the rename, and the parent-directory fsync) are offloaded
+//! onto a blocking thread pool via [`tokio::task::spawn_blocking`] so the
+//! reactor is never blocked.
+use crate::{imp, BoxError, Durability, Error, FinalPath, TempFilePath};
+use std::path::Path;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::fs::File;
+use tokio::io::{self, AsyncRead, AsyncSeek, AsyncWrite, ReadBuf};
+use tokio::task;
+
+fn join_err(error: task::JoinError) -> Error {
+    Error::Other(BoxError(Box::new(error)))
+}
+
+#[derive(Debug)]
+enum State {
+    Staged {
+        temp_file: File,
+        temp_dir: tempfile::TempDir,
+        temp_file_path: TempFilePath,
+    },
+    Committed,
+}
+
+/// Creates a temporary file which can then be committed to a final path from
+/// within an async executor.
+#[derive(Debug)]
+pub struct AsyncStagedFile {
+    final_path: FinalPath,
+    state: State,
+    preserve_existing_metadata: bool,
+    durability: Durability,
+}
+
+impl Drop for AsyncStagedFile {
+    fn drop(&mut self) {
+        let mut state = State::Committed;
+        std::mem::swap(&mut self.state, &mut state);
+        if let State::Staged {
+            temp_file,
+            temp_dir,
+            temp_file_path: _temp_file_path,
+        } = state
+        {
+            drop(temp_file);
+            drop(temp_dir);
+        }
+    }
+}
+
+impl AsyncStagedFile {
+    /// Instantiates a new staged file with the desired final path.
+    ///
+    /// See [`StagedFile::with_final_path`](crate::StagedFile::with_final_path)
+    /// for details; the temporary directory and file are created on a blocking
+    /// thread.
+    ///
+    /// # Errors
+    ///
+    /// If the final path is invalid (e.g. is a directory) or if the final path's
+    /// parent directory cannot be determined, an [`Error`] will be returned.
+    ///
+    /// Any I/O error which occurs when creating the temporary directory or file
+    /// will also be returned.
+    pub async fn with_final_path<P>(final_path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Self::with_final_path_and_temp_dir_prefix(final_path, None).await
+    }
+
+    /// Instantiates a new staged file with the desired final path and a
+    /// temporary directory prefix.
+    ///
+    /// See
+    /// [`StagedFile::with_final_path_and_temp_dir_prefix`](crate::StagedFile::with_final_path_and_temp_dir_prefix)
+    /// for details; the temporary directory and file are created on a blocking
+    /// thread.
+    ///
+    /// # Errors
+    ///
+    /// If the final path is invalid (e.g. is a directory) or if the final path's
+    /// parent directory cannot be determined, an [`Error`] will be returned.
+    ///
+    /// Any I/O error which occurs when creating the temporary directory or file
+    /// will also be returned.
+    pub async fn with_final_path_and_temp_dir_prefix<P>(
+        final_path: P,
+        temp_dir_prefix: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        let final_path = final_path.as_ref().to_path_buf();
+        let temp_dir_prefix = temp_dir_prefix.map(str::to_owned);
+
+        let (temp_file, temp_dir, temp_file_path, final_path) =
+            task::spawn_blocking(move || -> Result<_, Error> {
+                if final_path.is_dir() {
+                    return Err(Error::InvalidFinalPath);
+                }
+                let temp_dir = tempfile::Builder::new()
+                    .prefix(temp_dir_prefix.as_deref().unwrap_or(".staged"))
+                    .tempdir_in(crate::final_path_parent(&final_path)?)?;
+                let temp_file_path = temp_dir
+                    .path()
+                    .join(final_path.file_name().ok_or(Error::InvalidFinalPath)?);
+                let temp_file = std::fs::File::create(&temp_file_path)?;
+                Ok((
+                    temp_file,
+                    temp_dir,
+                    TempFilePath(temp_file_path),
+                    final_path,
+                ))
+            })
+            .await
+            .map_err(join_err)??;
+
+        Ok(Self {
+            final_path: FinalPath(final_path),
+            state: State::Staged {
+                temp_file: File::from_std(temp_file),
+                temp_dir,
+                temp_file_path,
+            },
+            preserve_existing_metadata: false,
+            durability: Durability::Full,
+        })
+    }
+
+    /// Sets the durability policy used when committing.
+    ///
+    /// See [`StagedFile::durability`](crate::StagedFile::durability).
+    #[must_use]
+    pub fn durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Preserves the permissions, owner, and group of any file already present
+    /// at the final path.
+    ///
+    /// See
+    /// [`StagedFile::preserve_existing_metadata`](crate::StagedFile::preserve_existing_metadata).
+    #[must_use]
+    pub fn preserve_existing_metadata(mut self) -> Self {
+        self.preserve_existing_metadata = true;
+        self
+    }
+
+    /// Commits the temporary file contents into the desired final path.
+    ///
+    /// See [`StagedFile::commit`](crate::StagedFile::commit); the `sync_all`,
+    /// rename, and parent-directory fsync are performed on a blocking thread.
+    ///
+    /// # Errors
+    ///
+    /// Any I/O errors encountered will be returned.
+    pub async fn commit(mut self) -> Result<(), Error> {
+        let mut state = State::Committed;
+        std::mem::swap(&mut self.state, &mut state);
+        if let State::Staged {
+            temp_file,
+            temp_dir,
+            temp_file_path,
+        } = state
+        {
+            let final_path = FinalPath(self.final_path.0.clone());
+            let preserve_existing_metadata = self.preserve_existing_metadata;
+            let durability = self.durability;
+
+            match durability {
+                Durability::Full => temp_file.sync_all().await?,
+                Durability::DataOnly => temp_file.sync_data().await?,
+                Durability::None => {}
+            }
+            // Explicit drop to remove any open file descriptors so temp dir can be deleted
+            drop(temp_file.into_std().await);
+
+            task::spawn_blocking(move || -> Result<(), Error> {
+                imp::commit(
+                    &temp_file_path,
+                    &final_path,
+                    preserve_existing_metadata,
+                    durability,
+                )?;
+                drop(temp_dir);
+                Ok(())
+            })
+            .await
+            .map_err(join_err)??;
+
+            Ok(())
+        } else {
+            unreachable!()
+        }
+    }
+
+    #[inline]
+    fn as_file_mut(self: Pin<&mut Self>) -> Pin<&mut File> {
+        let this = self.get_mut();
+        if let State::Staged {
+            ref mut temp_file, ..
+        } = this.state
+        {
+            Pin::new(temp_file)
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+impl AsyncRead for AsyncStagedFile {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.as_file_mut().poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for AsyncStagedFile {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.as_file_mut().poll_write(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.as_file_mut().poll_flush(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.as_file_mut().poll_shutdown(cx)
+    }
+
+    fn poll_write_vectored(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        self.as_file_mut().poll_write_vectored(cx, bufs)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        if let State::Staged { ref temp_file, .. } = self.state {
+            temp_file.is_write_vectored()
+        } else {
+            unreachable!()
+        }
+    }
+}
+
+impl AsyncSeek for AsyncStagedFile {
+    fn start_seek(self: Pin<&mut Self>, position: io::SeekFrom) -> io::Result<()> {
+        self.as_file_mut().start_seek(position)
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<u64>> {
+        self.as_file_mut().poll_complete(cx)
+    }
+}
@@ -2,5 +2,8 @@ cfg_if::cfg_if! {
     if #[cfg(any(unix))] {
         mod unix;
         pub(crate) use self::unix::*;
+    } else if #[cfg(windows)] {
+        mod windows;
+        pub(crate) use self::windows::*;
     }
 }
@@ -1,5 +1,6 @@
-use crate::{BoxError, Error, FinalPath, TempFilePath};
+use crate::{BoxError, Durability, Error, ErrorKind, FinalPath, TempFilePath};
 use std::fs;
+use std::io;
 
 impl From<nix::Error> for crate::Error {
     fn from(error: nix::Error) -> Self {
@@ -7,19 +8,61 @@ impl From<nix::Error> for crate::Error {
     }
 }
 
-pub(crate) fn commit(from: &TempFilePath, to: &FinalPath) -> Result<(), Error> {
+pub(crate) fn commit(
+    from: &TempFilePath,
+    to: &FinalPath,
+    preserve_existing_metadata: bool,
+    durability: Durability,
+) -> Result<(), Error> {
+    use std::os::unix::fs::MetadataExt;
     use std::os::unix::io::AsRawFd;
 
     let from = from.0.as_path();
     let to = to.0.as_path();
-    fs::rename(from, to)?;
 
-    let to_parent = to.parent().ok_or(Error::InvalidParentFinalPath)?;
-    debug_assert!(to_parent.is_dir());
+    if preserve_existing_metadata {
+        if let Ok(metadata) = fs::metadata(to) {
+            use nix::sys::stat::{fchmod, Mode};
+            use nix::unistd::{fchown, Gid, Uid};
 
-    let to_parent = fs::File::open(to_parent)?;
+            let temp_file = fs::File::open(from)?;
+            let fd = temp_file.as_raw_fd();
+            fchmod(fd, Mode::from_bits_truncate(metadata.mode()))?;
+            fchown(
+                fd,
+                Some(Uid::from_raw(metadata.uid())),
+                Some(Gid::from_raw(metadata.gid())),
+            )?;
+        }
+    }
+
+    fs::rename(from, to).map_err(|e| {
+        Error::op(
+            ErrorKind::Rename {
+                to: to.to_path_buf(),
+            },
+            from,
+            e,
+        )
+    })?;
+
+    // `Durability::None` skips the parent-directory fsync; the other policies
+    // still need it so the rename itself is durable.
+    if durability != Durability::None {
+        let to_parent = to.parent().ok_or(Error::InvalidParentFinalPath)?;
+        debug_assert!(to_parent.is_dir());
 
-    nix::unistd::fsync(to_parent.as_raw_fd())?;
+        let to_parent_file = fs::File::open(to_parent)
+            .map_err(|e| Error::op(ErrorKind::FsyncParent, to_parent, e))?;
+
+        nix::unistd::fsync(to_parent_file.as_raw_fd()).map_err(|e| {
+            Error::op(
+                ErrorKind::FsyncParent,
+                to_parent,
+                io::Error::from_raw_os_error(e as i32),
+            )
+        })?;
+    }
 
     Ok(())
 }
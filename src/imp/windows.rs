@@ -0,0 +1,83 @@
+use crate::{Durability, Error, ErrorKind, FinalPath, TempFilePath};
+use std::ffi::OsStr;
+use std::fs;
+use std::os::windows::ffi::OsStrExt;
+
+use windows_sys::Win32::Storage::FileSystem::{
+    MoveFileExW, ReplaceFileW, MOVEFILE_REPLACE_EXISTING, MOVEFILE_WRITE_THROUGH,
+};
+
+fn wide(path: &std::path::Path) -> Vec<u16> {
+    OsStr::new(path)
+        .encode_wide()
+        .chain(std::iter::once(0))
+        .collect()
+}
+
+pub(crate) fn commit(
+    from: &TempFilePath,
+    to: &FinalPath,
+    _preserve_existing_metadata: bool,
+    durability: Durability,
+) -> Result<(), Error> {
+    let from = from.0.as_path();
+    let to = to.0.as_path();
+
+    // `fs::rename` (and `MoveFileExW` without `MOVEFILE_WRITE_THROUGH`) are not
+    // atomic on Windows when the destination exists. Flush the temp file's data
+    // to disk (skipped for `Durability::None`), then replace an existing
+    // destination with `ReplaceFileW`, or move into a new destination with a
+    // write-through `MoveFileExW`.
+    if durability != Durability::None {
+        fs::File::open(from)?.sync_all()?;
+    }
+
+    let from_wide = wide(from);
+    let to_wide = wide(to);
+
+    if to.exists() {
+        // When the destination already exists, `ReplaceFileW` preserves the
+        // original file's attributes and ACLs across the replacement.
+        let result = unsafe {
+            ReplaceFileW(
+                to_wide.as_ptr(),
+                from_wide.as_ptr(),
+                std::ptr::null(),
+                0,
+                std::ptr::null(),
+                std::ptr::null(),
+            )
+        };
+        if result == 0 {
+            return Err(Error::op(
+                ErrorKind::Rename {
+                    to: to.to_path_buf(),
+                },
+                from,
+                std::io::Error::last_os_error(),
+            ));
+        }
+    } else {
+        let result = unsafe {
+            MoveFileExW(
+                from_wide.as_ptr(),
+                to_wide.as_ptr(),
+                MOVEFILE_REPLACE_EXISTING | MOVEFILE_WRITE_THROUGH,
+            )
+        };
+        if result == 0 {
+            return Err(Error::op(
+                ErrorKind::Rename {
+                    to: to.to_path_buf(),
+                },
+                from,
+                std::io::Error::last_os_error(),
+            ));
+        }
+    }
+
+    // Windows has no concept of fsync-ing a directory handle, so the parent-dir
+    // fsync performed by the unix implementation is skipped here.
+
+    Ok(())
+}
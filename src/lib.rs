@@ -61,6 +61,28 @@ impl std::error::Error for BoxError {
     }
 }
 
+/// The operation which failed when an I/O error occurred.
+///
+/// Paired with the offending path in [`Error::Op`] so callers can tell which
+/// step of staging or committing failed.
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// Creating the temporary directory.
+    CreateTempDir,
+    /// Creating the temporary file.
+    CreateTempFile,
+    /// Syncing the temporary file's contents to disk.
+    Sync,
+    /// Renaming the temporary file over the final path. Carries the final path.
+    Rename {
+        /// The final path the temporary file was being renamed to.
+        to: PathBuf,
+    },
+    /// Syncing the final path's parent directory.
+    FsyncParent,
+}
+
 /// Possible errors when creating and committing the staged file.
 #[derive(Debug)]
 pub enum Error {
@@ -68,17 +90,62 @@ pub enum Error {
     InvalidFinalPath,
     /// The parent directory of the final path is not valid (e.g. cannot be accessed or determined).
     InvalidParentFinalPath,
+    /// An I/O error which occurred during a known operation on a known path.
+    Op {
+        /// The operation which failed.
+        kind: ErrorKind,
+        /// The path the operation was performed on.
+        path: PathBuf,
+        /// The underlying I/O error.
+        source: io::Error,
+    },
     /// An I/O error.
     Io(io::Error),
     /// All other errors.
     Other(BoxError),
 }
 
+impl Error {
+    pub(crate) fn op(kind: ErrorKind, path: impl Into<PathBuf>, source: io::Error) -> Self {
+        Error::Op {
+            kind,
+            path: path.into(),
+            source,
+        }
+    }
+}
+
 impl Display for Error {
     fn fmt(&self, f: &mut Formatter<'_>) -> result::Result<(), fmt::Error> {
         match self {
             Error::InvalidFinalPath => write!(f, "invalid final path"),
             Error::InvalidParentFinalPath => write!(f, "invalid parent final path"),
+            Error::Op { kind, path, source } => match kind {
+                ErrorKind::CreateTempDir => write!(
+                    f,
+                    "failed to create temporary directory in {}: {source}",
+                    path.display()
+                ),
+                ErrorKind::CreateTempFile => write!(
+                    f,
+                    "failed to create temporary file {}: {source}",
+                    path.display()
+                ),
+                ErrorKind::Sync => {
+                    write!(f, "failed to sync {}: {source}", path.display())
+                }
+                ErrorKind::Rename { to } => write!(
+                    f,
+                    "failed to rename {} to {}: {source}",
+                    path.display(),
+                    to.display()
+                ),
+                ErrorKind::FsyncParent => write!(
+                    f,
+                    "failed to fsync parent directory {}: {source}",
+                    path.display()
+                ),
+            },
             Error::Io(e) => e.fmt(f),
             Error::Other(e) => e.fmt(f),
         }
@@ -90,6 +157,7 @@ impl std::error::Error for Error {
         match self {
             Error::InvalidFinalPath => None,
             Error::InvalidParentFinalPath => None,
+            Error::Op { source, .. } => Some(source),
             Error::Io(e) => Some(e),
             Error::Other(e) => Some(e),
         }
@@ -125,11 +193,38 @@ enum State {
     Committed,
 }
 
+/// Controls how aggressively [`StagedFile::commit()`] flushes data to disk.
+///
+/// Crash-safety guarantees come at the cost of one or more `fsync`-class
+/// syscalls. Callers writing many small files where the durability guarantees
+/// are not required can trade them for throughput explicitly rather than
+/// forking the crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Durability {
+    /// Fully flush the temporary file's data and metadata (`sync_all`) and
+    /// fsync the parent directory so the rename is durable across a crash.
+    ///
+    /// This is the default and the only policy that guarantees the committed
+    /// file survives a power loss.
+    #[default]
+    Full,
+    /// Flush only the temporary file's data (`fdatasync`/`sync_data`), skipping
+    /// the metadata flush, and still fsync the parent directory.
+    DataOnly,
+    /// Skip the file and parent-directory syncs entirely.
+    ///
+    /// The rename is still performed, but neither the file contents nor the
+    /// directory entry are guaranteed to be durable after a crash.
+    None,
+}
+
 /// Creates a temporary file which can then be committed to a final path.
 #[derive(Debug)]
 pub struct StagedFile {
     final_path: FinalPath,
     state: State,
+    preserve_existing_metadata: bool,
+    durability: Durability,
 }
 
 impl Drop for StagedFile {
@@ -186,7 +281,9 @@ impl StagedFile {
     ///
     /// # Important
     ///
-    /// If a file exists at the desired final file path, it will be overwritten.
+    /// The temporary file starts empty, so committing will overwrite (truncate)
+    /// any file already present at the final path. To instead start from the
+    /// existing contents, use [`StagedFile::edit_final_path()`].
     ///
     /// # Errors
     ///
@@ -202,17 +299,102 @@ impl StagedFile {
     where
         P: AsRef<Path>,
     {
-        let final_path = final_path.as_ref();
+        Self::stage(final_path.as_ref(), temp_dir_prefix, false)
+    }
+
+    /// Instantiates a new staged file seeded with the current contents of the
+    /// file already at the desired final path.
+    ///
+    /// Unlike [`StagedFile::with_final_path()`], which starts from an empty
+    /// temporary file and therefore truncates the final path on commit, this
+    /// constructor copies the existing file's contents into the temporary file
+    /// and leaves the cursor at offset `0`. This makes it suitable for
+    /// read-modify-write (update-in-place) workflows: callers can seek and read
+    /// the current bytes, overwrite parts of them, and commit without losing
+    /// data they did not explicitly rewrite.
+    ///
+    /// If no file exists at the final path yet, the temporary file starts empty,
+    /// matching [`StagedFile::with_final_path()`].
+    ///
+    /// # Errors
+    ///
+    /// If the final path is invalid (e.g. is a directory) or if the final path's
+    /// parent directory cannot be determined, an [`Error`] will be returned.
+    ///
+    /// Any I/O error which occurs when creating the temporary directory or file,
+    /// or when copying the existing contents, will also be returned.
+    pub fn edit_final_path<P>(final_path: P) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Self::edit_final_path_and_temp_dir_prefix(final_path, None)
+    }
+
+    /// Instantiates a new staged file seeded with the current contents of the
+    /// file already at the desired final path, using a temporary directory
+    /// prefix.
+    ///
+    /// See [`StagedFile::edit_final_path()`] for the seeding behavior and
+    /// [`StagedFile::with_final_path_and_temp_dir_prefix()`] for the temporary
+    /// directory prefix behavior.
+    ///
+    /// # Errors
+    ///
+    /// If the final path is invalid (e.g. is a directory) or if the final path's
+    /// parent directory cannot be determined, an [`Error`] will be returned.
+    ///
+    /// Any I/O error which occurs when creating the temporary directory or file,
+    /// or when copying the existing contents, will also be returned.
+    pub fn edit_final_path_and_temp_dir_prefix<P>(
+        final_path: P,
+        temp_dir_prefix: Option<&str>,
+    ) -> Result<Self, Error>
+    where
+        P: AsRef<Path>,
+    {
+        Self::stage(final_path.as_ref(), temp_dir_prefix, true)
+    }
+
+    fn stage(
+        final_path: &Path,
+        temp_dir_prefix: Option<&str>,
+        seed_from_existing: bool,
+    ) -> Result<Self, Error> {
         if final_path.is_dir() {
             return Err(Error::InvalidFinalPath);
         }
+        let parent = final_path_parent(final_path)?;
         let temp_dir = tempfile::Builder::new()
             .prefix(temp_dir_prefix.unwrap_or(".staged"))
-            .tempdir_in(final_path_parent(final_path)?)?;
+            .tempdir_in(parent)
+            .map_err(|e| Error::op(ErrorKind::CreateTempDir, parent, e))?;
         let temp_file_path = temp_dir
             .path()
             .join(final_path.file_name().ok_or(Error::InvalidFinalPath)?);
-        let temp_file = File::create(&temp_file_path)?;
+
+        let temp_file = if seed_from_existing {
+            use io::{Seek, SeekFrom};
+
+            let mut temp_file = std::fs::OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(true)
+                .truncate(true)
+                .open(&temp_file_path)
+                .map_err(|e| Error::op(ErrorKind::CreateTempFile, &temp_file_path, e))?;
+            match File::open(final_path) {
+                Ok(mut existing) => {
+                    io::copy(&mut existing, &mut temp_file)?;
+                    temp_file.seek(SeekFrom::Start(0))?;
+                }
+                Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+                Err(e) => return Err(Error::Io(e)),
+            }
+            temp_file
+        } else {
+            File::create(&temp_file_path)
+                .map_err(|e| Error::op(ErrorKind::CreateTempFile, &temp_file_path, e))?
+        };
 
         Ok(Self {
             final_path: FinalPath(final_path.to_path_buf()),
@@ -221,9 +403,41 @@ impl StagedFile {
                 temp_dir,
                 temp_file_path: TempFilePath(temp_file_path),
             },
+            preserve_existing_metadata: false,
+            durability: Durability::Full,
         })
     }
 
+    /// Sets the durability policy used when committing.
+    ///
+    /// Defaults to [`Durability::Full`]. See [`Durability`] for the trade-offs.
+    #[must_use]
+    pub fn durability(mut self, durability: Durability) -> Self {
+        self.durability = durability;
+        self
+    }
+
+    /// Preserves the permissions, owner, and group of any file already present
+    /// at the final path.
+    ///
+    /// By default, the committed file inherits whatever mode the temporary file
+    /// was created with (that is, `File::create` plus the process umask). When
+    /// this flag is set and a file already exists at the final path, its mode
+    /// bits and owner/group are applied to the temporary file before the rename
+    /// so that rewriting an existing file does not silently downgrade its
+    /// metadata.
+    ///
+    /// If no file exists at the final path, the default behavior is used.
+    ///
+    /// On Windows this flag is ignored: an existing file at the final path is
+    /// always replaced with `ReplaceFileW`, which keeps that file's attributes
+    /// and ACLs.
+    #[must_use]
+    pub fn preserve_existing_metadata(mut self) -> Self {
+        self.preserve_existing_metadata = true;
+        self
+    }
+
     /// Commits the temporary file contents into the desired final path.
     ///
     /// If the contents should *not* be committed, then allow the `StagedFile` to
@@ -245,11 +459,24 @@ impl StagedFile {
             temp_file_path,
         } = state
         {
-            temp_file.sync_all()?;
+            match self.durability {
+                Durability::Full => temp_file
+                    .sync_all()
+                    .map_err(|e| Error::op(ErrorKind::Sync, &temp_file_path.0, e))?,
+                Durability::DataOnly => temp_file
+                    .sync_data()
+                    .map_err(|e| Error::op(ErrorKind::Sync, &temp_file_path.0, e))?,
+                Durability::None => {}
+            }
             // Explicit drop to remove any open file descriptors so temp dir can be deleted
             drop(temp_file);
 
-            imp::commit(&temp_file_path, &self.final_path)?;
+            imp::commit(
+                &temp_file_path,
+                &self.final_path,
+                self.preserve_existing_metadata,
+                self.durability,
+            )?;
 
             drop(temp_dir);
 
@@ -379,6 +606,11 @@ impl io::Read for &StagedFile {
 
 pub(crate) mod imp;
 
+#[cfg(feature = "tokio")]
+mod async_file;
+#[cfg(feature = "tokio")]
+pub use async_file::AsyncStagedFile;
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -423,4 +655,26 @@ mod test {
 
         assert!(!final_path.exists());
     }
+
+    #[test]
+    fn edit_seeds_existing_contents() {
+        use std::io::prelude::*;
+
+        let temp_dir = tempfile::tempdir().unwrap();
+        let final_path = temp_dir.path().join("test3");
+        std::fs::write(&final_path, b"Hello World!").unwrap();
+
+        let mut staged_file = StagedFile::edit_final_path(&final_path).unwrap();
+
+        let mut contents = Vec::new();
+        staged_file.read_to_end(&mut contents).unwrap();
+        assert_eq!(contents, b"Hello World!");
+
+        staged_file.seek(std::io::SeekFrom::Start(0)).unwrap();
+        staged_file.write_all(b"Howdy").unwrap();
+
+        staged_file.commit().unwrap();
+
+        assert_eq!(std::fs::read(&final_path).unwrap(), b"Howdy World!");
+    }
 }